@@ -1,7 +1,13 @@
 use std::collections::HashMap;
 use std::fmt::{Display,Debug,Error,Formatter};
+use std::io::{self, Read, Write};
 use std::iter::Peekable;
 
+#[cfg(feature = "bigint")]
+use num_bigint::BigInt;
+#[cfg(feature = "bigint")]
+use num_traits::ToPrimitive;
+
 #[derive(PartialEq, Eq, Hash)]
 pub struct ByteString(pub Vec<u8>);
 
@@ -31,6 +37,8 @@ impl ToByteString for str {
 #[derive(PartialEq, Debug)]
 pub enum BenObject {
     I(i64),
+    #[cfg(feature = "bigint")]
+    Big(BigInt),
     S(ByteString),
     L(Vec<BenObject>),
     D(HashMap<ByteString, BenObject>)
@@ -41,6 +49,17 @@ impl BenObject {
     pub fn as_int(&self) -> Option<i64> {
         match *self {
             BenObject::I(x) => Some(x),
+            #[cfg(feature = "bigint")]
+            BenObject::Big(ref b) => b.to_i64(),
+            _ => None
+        }
+    }
+
+    #[cfg(feature = "bigint")]
+    pub fn as_bigint(&self) -> Option<BigInt> {
+        match *self {
+            BenObject::I(x) => Some(BigInt::from(x)),
+            BenObject::Big(ref b) => Some(b.clone()),
             _ => None
         }
     }
@@ -66,6 +85,45 @@ impl BenObject {
         }
     }
 
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        // Writing into a `Vec<u8>` is infallible, so the result is safe to unwrap.
+        self.encode_to(&mut out).unwrap();
+        out
+    }
+
+    pub fn encode_to(&self, out: &mut dyn Write) -> io::Result<()> {
+        match *self {
+            BenObject::I(x) => write!(out, "i{}e", x),
+            #[cfg(feature = "bigint")]
+            BenObject::Big(ref b) => write!(out, "i{}e", b),
+            BenObject::S(ByteString(ref buf)) => {
+                write!(out, "{}:", buf.len())?;
+                out.write_all(buf)
+            },
+            BenObject::L(ref list) => {
+                out.write_all(b"l")?;
+                for obj in list {
+                    obj.encode_to(out)?;
+                }
+                out.write_all(b"e")
+            },
+            BenObject::D(ref dict) => {
+                out.write_all(b"d")?;
+                // The canonical encoding requires dictionary keys in ascending
+                // byte order; `HashMap` iteration order is arbitrary, so sort.
+                let mut keys: Vec<&ByteString> = dict.keys().collect();
+                keys.sort_by(|a, b| a.0.cmp(&b.0));
+                for key in keys {
+                    write!(out, "{}:", key.0.len())?;
+                    out.write_all(&key.0)?;
+                    dict[key].encode_to(out)?;
+                }
+                out.write_all(b"e")
+            }
+        }
+    }
+
     pub fn decode<I>(bytes: &mut I) -> Result<Self, String>
         where I: Iterator<Item=u8>
     {
@@ -89,22 +147,17 @@ impl BenObject {
     fn decode_bendict<I>(bytes: &mut Peekable<I>) -> Result<Self, String>
         where I: Iterator<Item=u8>
     {
-        assert_eq!(bytes.next().unwrap(), 'd' as u8);
+        assert_eq!(bytes.next().unwrap(), b'd');
+        // Repeated keys follow last-entry-wins: a later occurrence overrides an
+        // earlier one. The `Decoder` offers a strict mode to reject duplicates.
         let mut hash = HashMap::new();
-        while bytes.peek() != Some(&('e' as u8)) {
-            let benstr = Self::decode_benstring(bytes);
-            if benstr.is_err() {
-                return benstr
-            }
-            let key = match benstr.unwrap() {
+        while bytes.peek() != Some(&b'e') {
+            let key = match Self::decode_benstring(bytes)? {
                 BenObject::S(k) => k,
                 _ => panic!("unexpected  type")
             };
-            let benobj = Self::decode_benobject(bytes);
-            if benobj.is_err() {
-                return benobj
-            }
-            hash.insert(key, benobj.unwrap());
+            let benobj = Self::decode_benobject(bytes)?;
+            hash.insert(key, benobj);
         }
         if Self::skip_if_match(bytes, 'e') {
             Ok(BenObject::D(hash))
@@ -117,7 +170,7 @@ impl BenObject {
     fn decode_benint<I>(bytes: &mut Peekable<I>) -> Result<Self, String>
         where I: Iterator<Item=u8>
     {
-        assert_eq!(bytes.next().unwrap(), 'i' as u8);
+        assert_eq!(bytes.next().unwrap(), b'i');
         let sign = if Self::skip_if_match(bytes, '-') { -1 } else { 1 };
         let val = sign * Self::decode_uint(bytes) as i64;
         if Self::skip_if_match(bytes, 'e') {
@@ -130,14 +183,10 @@ impl BenObject {
     fn decode_benlist<I>(bytes: &mut Peekable<I>) -> Result<Self, String>
         where I: Iterator<Item=u8>
     {
-        assert_eq!(bytes.next().unwrap(), 'l' as u8);
+        assert_eq!(bytes.next().unwrap(), b'l');
         let mut vec = Vec::new();
-        while bytes.peek() != Some(&('e' as u8)) {
-            let benobj = Self::decode_benobject(bytes);
-            if benobj.is_err() {
-                return benobj
-            }
-            vec.push(benobj.unwrap())
+        while bytes.peek() != Some(&b'e') {
+            vec.push(Self::decode_benobject(bytes)?)
         }
         if Self::skip_if_match(bytes, 'e') {
             Ok(BenObject::L(vec))
@@ -178,11 +227,555 @@ impl BenObject {
         where I: Iterator<Item=u8>
     {
         let mut num = 0;
-        while bytes.peek().map_or(false, |c| (*c as char).is_digit(10)) {
+        while bytes.peek().is_some_and(|c| (*c as char).is_ascii_digit()) {
             num *= 10;
-            num += (bytes.next().unwrap() - '0' as u8) as u64
+            num += (bytes.next().unwrap() - b'0') as u64
         }
         num
     }
 
 }
+
+#[derive(Debug)]
+pub enum DecodeError {
+    Io(io::Error),
+    Syntax(&'static str),
+    Eof
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match *self {
+            DecodeError::Io(ref e) => write!(f, "io error: {}", e),
+            DecodeError::Syntax(msg) => write!(f, "syntax error: {}", msg),
+            DecodeError::Eof => write!(f, "unexpected end of input")
+        }
+    }
+}
+
+impl From<io::Error> for DecodeError {
+    fn from(e: io::Error) -> Self {
+        DecodeError::Io(e)
+    }
+}
+
+/// What to do when a dictionary contains a repeated key.
+#[derive(Clone, Copy, PartialEq)]
+pub enum DuplicateKeyPolicy {
+    /// Keep the last occurrence of the key (the natural `HashMap::insert`
+    /// result). This is the documented, guaranteed default.
+    LastWins,
+    /// Treat any repeated key as a hard decode error.
+    Error
+}
+
+/// Knobs controlling how strictly input is validated while decoding.
+#[derive(Clone, Copy)]
+pub struct DecodeOptions {
+    pub strict: bool,
+    pub on_duplicate_key: DuplicateKeyPolicy
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        DecodeOptions { strict: false, on_duplicate_key: DuplicateKeyPolicy::LastWins }
+    }
+}
+
+/// Decodes bencode straight off an arbitrary `Read`, so a `.torrent` can be
+/// parsed from a `File` or socket without buffering the whole thing first.
+///
+/// A single byte of lookahead is kept so that the porting of `peek`/`skip`
+/// carries over unchanged. The buffer is an `Option<Option<u8>>`: the outer
+/// `None` means "not primed yet", the inner `None` means EOF.
+pub struct Decoder<R: Read> {
+    reader: R,
+    lookahead: Option<Option<u8>>,
+    pos: u64,
+    options: DecodeOptions
+}
+
+/// Structural span information mirroring a decoded `BenObject`.
+pub enum SpanNode {
+    /// An integer or byte string: carries no nested values.
+    Leaf,
+    /// A list, with the span of each element in order.
+    List(Vec<Span>),
+    /// A dictionary, with each key and the span of its value, in parsed order.
+    Dict(Vec<(ByteString, Span)>)
+}
+
+/// The `[start, end)` byte offsets of a value's exact on-the-wire encoding,
+/// together with the spans of any nested values.
+///
+/// Locating the `b"info"` entry of the top-level dict and slicing the original
+/// input over its span yields the verbatim bytes to feed to a SHA-1 hasher for
+/// the info_hash, sidestepping lossy re-encoding through a `HashMap`.
+pub struct Span {
+    pub start: u64,
+    pub end: u64,
+    pub node: SpanNode
+}
+
+impl<R: Read> Decoder<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_options(reader, DecodeOptions::default())
+    }
+
+    pub fn with_options(reader: R, options: DecodeOptions) -> Self {
+        Decoder { reader, lookahead: None, pos: 0, options }
+    }
+
+    pub fn decode(&mut self) -> Result<BenObject, DecodeError> {
+        self.decode_benobject()
+    }
+
+    /// Decodes one value and, alongside the tree, the `[start, end)` byte span
+    /// of it and every nested value. Byte offsets are counted from the first
+    /// byte handed to this decoder.
+    pub fn decode_with_spans(&mut self) -> Result<(BenObject, Span), DecodeError> {
+        self.decode_spanned()
+    }
+
+    /// Number of input bytes consumed so far.
+    pub fn position(&self) -> u64 {
+        self.pos
+    }
+
+    fn prime(&mut self) -> Result<(), DecodeError> {
+        if self.lookahead.is_none() {
+            let mut buf = [0u8; 1];
+            let n = self.reader.read(&mut buf)?;
+            self.lookahead = Some(if n == 0 { None } else { Some(buf[0]) });
+        }
+        Ok(())
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, DecodeError> {
+        self.prime()?;
+        Ok(self.lookahead.unwrap())
+    }
+
+    fn skip(&mut self) {
+        self.lookahead = None;
+        self.pos += 1;
+    }
+
+    fn next_byte(&mut self) -> Result<u8, DecodeError> {
+        match self.peek()? {
+            Some(b) => { self.skip(); Ok(b) },
+            None => Err(DecodeError::Eof)
+        }
+    }
+
+    fn skip_if_match(&mut self, ch: char) -> Result<bool, DecodeError> {
+        if self.peek()? == Some(ch as u8) {
+            self.skip();
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn decode_benobject(&mut self) -> Result<BenObject, DecodeError> {
+        match self.peek()? {
+            Some(c) => match c as char {
+                'd' => self.decode_bendict(),
+                'i' => self.decode_benint(),
+                'l' => self.decode_benlist(),
+                _ => self.decode_benstring()
+            },
+            None => Err(DecodeError::Eof)
+        }
+    }
+
+    fn decode_bendict(&mut self) -> Result<BenObject, DecodeError> {
+        self.skip(); // 'd'
+        let mut hash = HashMap::new();
+        let mut last_key: Option<Vec<u8>> = None;
+        while self.peek()? != Some(b'e') {
+            let key = match self.decode_benstring()? {
+                BenObject::S(k) => k,
+                _ => return Err(DecodeError::Syntax("dict key is not a byte string"))
+            };
+            self.check_key_order(&last_key, &key.0)?;
+            last_key = Some(key.0.clone());
+            let value = self.decode_benobject()?;
+            if self.options.on_duplicate_key == DuplicateKeyPolicy::Error && hash.contains_key(&key) {
+                return Err(DecodeError::Syntax("duplicate dict key"));
+            }
+            // Otherwise last-entry-wins: the later value overrides the earlier.
+            hash.insert(key, value);
+        }
+        self.skip(); // 'e'
+        Ok(BenObject::D(hash))
+    }
+
+    fn decode_benint(&mut self) -> Result<BenObject, DecodeError> {
+        self.skip(); // 'i'
+        let negative = self.skip_if_match('-')?;
+        let digits = self.read_digits()?;
+        if self.options.strict {
+            if digits.is_empty() {
+                return Err(DecodeError::Syntax("integer has no digits"));
+            }
+            if digits.len() > 1 && digits[0] == b'0' {
+                return Err(DecodeError::Syntax("integer has leading zeros"));
+            }
+            if negative && digits == [b'0'] {
+                return Err(DecodeError::Syntax("negative zero is not allowed"));
+            }
+        }
+        let obj = self.make_int(negative, &digits)?;
+        if self.skip_if_match('e')? {
+            Ok(obj)
+        } else {
+            Err(DecodeError::Syntax("parsing integer failed: expected 'e'"))
+        }
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    fn make_int(&self, negative: bool, digits: &[u8]) -> Result<BenObject, DecodeError> {
+        Ok(BenObject::I(self.parse_int(negative, digits)?))
+    }
+
+    /// Accumulates the digits into a `BigInt` (the spec places no bound on
+    /// integer size) and narrows back to `I(i64)` whenever the value fits,
+    /// falling back to `Big` only for genuinely out-of-range values.
+    #[cfg(feature = "bigint")]
+    fn make_int(&self, negative: bool, digits: &[u8]) -> Result<BenObject, DecodeError> {
+        let mut acc = BigInt::from(0u32);
+        for &d in digits {
+            acc = acc * 10u32 + (d - b'0') as u32;
+        }
+        if negative {
+            acc = -acc;
+        }
+        Ok(match acc.to_i64() {
+            Some(v) => BenObject::I(v),
+            None => BenObject::Big(acc)
+        })
+    }
+
+    fn read_digits(&mut self) -> Result<Vec<u8>, DecodeError> {
+        let mut digits = Vec::new();
+        while let Some(c) = self.peek()? {
+            if !(c as char).is_ascii_digit() {
+                break;
+            }
+            self.skip();
+            digits.push(c);
+        }
+        Ok(digits)
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    fn parse_int(&self, negative: bool, digits: &[u8]) -> Result<i64, DecodeError> {
+        if self.options.strict {
+            // Accumulate with the sign already applied so that `i64::MIN`
+            // (whose magnitude has no positive `i64` representation) round-trips.
+            let mut acc: i64 = 0;
+            for &d in digits {
+                let digit = (d - b'0') as i64;
+                acc = acc.checked_mul(10)
+                    .and_then(|a| if negative { a.checked_sub(digit) } else { a.checked_add(digit) })
+                    .ok_or(DecodeError::Syntax("integer out of i64 range"))?;
+            }
+            Ok(acc)
+        } else {
+            // Preserve the historically permissive (wrapping) behavior. Negate
+            // via `wrapping_neg` rather than `sign * num as i64`: the latter
+            // panics in debug builds when `num as i64` is `i64::MIN`.
+            let mut num: u64 = 0;
+            for &d in digits {
+                num = num.wrapping_mul(10).wrapping_add((d - b'0') as u64);
+            }
+            let num = num as i64;
+            Ok(if negative { num.wrapping_neg() } else { num })
+        }
+    }
+
+    fn check_key_order(&self, last: &Option<Vec<u8>>, key: &[u8]) -> Result<(), DecodeError> {
+        if self.options.strict {
+            if let Some(ref prev) = *last {
+                if key == prev.as_slice() {
+                    return Err(DecodeError::Syntax("duplicate dict key"));
+                }
+                if key < prev.as_slice() {
+                    return Err(DecodeError::Syntax("dict keys not in ascending order"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn decode_benlist(&mut self) -> Result<BenObject, DecodeError> {
+        self.skip(); // 'l'
+        let mut vec = Vec::new();
+        while self.peek()? != Some(b'e') {
+            vec.push(self.decode_benobject()?);
+        }
+        self.skip(); // 'e'
+        Ok(BenObject::L(vec))
+    }
+
+    fn decode_benstring(&mut self) -> Result<BenObject, DecodeError> {
+        let len = self.decode_uint()? as usize;
+        if !self.skip_if_match(':')? {
+            return Err(DecodeError::Syntax("parsing string failed: expected ':'"));
+        }
+        // `len` comes straight from untrusted input, so don't let it dictate an
+        // eager allocation; cap the reserve and let the loop grow the buffer
+        // the rest of the way, erroring on a short read as before.
+        const MAX_RESERVE: usize = 64 * 1024;
+        let mut buf = Vec::with_capacity(len.min(MAX_RESERVE));
+        for _ in 0..len {
+            buf.push(self.next_byte()?);
+        }
+        Ok(BenObject::S(ByteString(buf)))
+    }
+
+    fn decode_uint(&mut self) -> Result<u64, DecodeError> {
+        let mut num = 0u64;
+        while let Some(c) = self.peek()? {
+            if !(c as char).is_ascii_digit() {
+                break;
+            }
+            self.skip();
+            // Saturate rather than overflow-panic on an absurdly long digit
+            // run; a saturated length still fails honestly as a short read
+            // instead of wrapping into something small and misleadingly valid.
+            num = num.saturating_mul(10).saturating_add((c - b'0') as u64);
+        }
+        Ok(num)
+    }
+
+    fn decode_spanned(&mut self) -> Result<(BenObject, Span), DecodeError> {
+        let start = self.pos;
+        match self.peek()? {
+            Some(c) => match c as char {
+                'd' => self.decode_bendict_spanned(start),
+                'l' => self.decode_benlist_spanned(start),
+                'i' => {
+                    let obj = self.decode_benint()?;
+                    Ok((obj, self.leaf_span(start)))
+                },
+                _ => {
+                    let obj = self.decode_benstring()?;
+                    Ok((obj, self.leaf_span(start)))
+                }
+            },
+            None => Err(DecodeError::Eof)
+        }
+    }
+
+    fn leaf_span(&self, start: u64) -> Span {
+        Span { start, end: self.pos, node: SpanNode::Leaf }
+    }
+
+    fn decode_bendict_spanned(&mut self, start: u64) -> Result<(BenObject, Span), DecodeError> {
+        self.skip(); // 'd'
+        let mut hash = HashMap::new();
+        let mut spans = Vec::new();
+        let mut last_key: Option<Vec<u8>> = None;
+        while self.peek()? != Some(b'e') {
+            let key = match self.decode_benstring()? {
+                BenObject::S(k) => k,
+                _ => return Err(DecodeError::Syntax("dict key is not a byte string"))
+            };
+            self.check_key_order(&last_key, &key.0)?;
+            last_key = Some(key.0.clone());
+            let (value, span) = self.decode_spanned()?;
+            if self.options.on_duplicate_key == DuplicateKeyPolicy::Error && hash.contains_key(&key) {
+                return Err(DecodeError::Syntax("duplicate dict key"));
+            }
+            // Otherwise last-entry-wins: the later value overrides the earlier.
+            spans.push((ByteString(key.0.clone()), span));
+            hash.insert(key, value);
+        }
+        self.skip(); // 'e'
+        Ok((BenObject::D(hash), Span { start, end: self.pos, node: SpanNode::Dict(spans) }))
+    }
+
+    fn decode_benlist_spanned(&mut self, start: u64) -> Result<(BenObject, Span), DecodeError> {
+        self.skip(); // 'l'
+        let mut vec = Vec::new();
+        let mut spans = Vec::new();
+        while self.peek()? != Some(b'e') {
+            let (obj, span) = self.decode_spanned()?;
+            vec.push(obj);
+            spans.push(span);
+        }
+        self.skip(); // 'e'
+        Ok((BenObject::L(vec), Span { start, end: self.pos, node: SpanNode::List(spans) }))
+    }
+}
+
+/// A single structural token yielded by [`Tokenizer`].
+#[derive(PartialEq, Debug)]
+pub enum Token {
+    IntegerValue(i64),
+    ByteStringValue(Vec<u8>),
+    ListStart,
+    DictStart,
+    End,
+    Eof
+}
+
+/// Pull parser that walks the input once, emitting one [`Token`] per structural
+/// element instead of building an owned tree.
+///
+/// Containers are framed by `ListStart`/`DictStart` and a matching `End`, so a
+/// caller can track nesting depth and skip over subtrees it doesn't care about
+/// (e.g. jump straight to `b"info"` -> `b"pieces"` and stream the piece hashes)
+/// while holding only the one value currently in hand. Dictionary keys surface
+/// as ordinary `ByteStringValue` tokens. This complements [`Decoder::decode`],
+/// which remains the convenient tree-building path.
+pub struct Tokenizer<R: Read> {
+    decoder: Decoder<R>,
+    done: bool
+}
+
+impl<R: Read> Tokenizer<R> {
+    pub fn new(reader: R) -> Self {
+        Tokenizer { decoder: Decoder::new(reader), done: false }
+    }
+
+    fn next_token(&mut self) -> Result<Token, DecodeError> {
+        match self.decoder.peek()? {
+            None => Ok(Token::Eof),
+            Some(c) => match c as char {
+                'e' => { self.decoder.skip(); Ok(Token::End) },
+                'd' => { self.decoder.skip(); Ok(Token::DictStart) },
+                'l' => { self.decoder.skip(); Ok(Token::ListStart) },
+                'i' => match self.decoder.decode_benint()?.as_int() {
+                    Some(v) => Ok(Token::IntegerValue(v)),
+                    None => Err(DecodeError::Syntax("integer out of i64 range"))
+                },
+                _ => match self.decoder.decode_benstring()? {
+                    BenObject::S(ByteString(buf)) => Ok(Token::ByteStringValue(buf)),
+                    _ => Err(DecodeError::Syntax("expected byte string"))
+                }
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for Tokenizer<R> {
+    type Item = Result<Token, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.next_token() {
+            Ok(Token::Eof) => { self.done = true; Some(Ok(Token::Eof)) },
+            Ok(tok) => Some(Ok(tok)),
+            Err(e) => { self.done = true; Some(Err(e)) }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn encode_sorts_dict_keys_canonically() {
+        let mut dict = HashMap::new();
+        dict.insert(ByteString(b"zebra".to_vec()), BenObject::I(1));
+        dict.insert(ByteString(b"apple".to_vec()), BenObject::I(2));
+        dict.insert(ByteString(b"mango".to_vec()), BenObject::I(3));
+        let obj = BenObject::D(dict);
+        assert_eq!(obj.encode(), b"d5:applei2e5:mangoi3e5:zebrai1ee".to_vec());
+    }
+
+    #[test]
+    fn decode_after_encode_round_trips() {
+        let mut dict = HashMap::new();
+        dict.insert(ByteString(b"list".to_vec()), BenObject::L(vec![
+            BenObject::I(-5),
+            BenObject::S(ByteString(b"hi".to_vec()))
+        ]));
+        let obj = BenObject::D(dict);
+        let bytes = obj.encode();
+        let decoded = Decoder::new(Cursor::new(bytes)).decode().unwrap();
+        assert_eq!(decoded, obj);
+    }
+
+    #[test]
+    fn strict_mode_round_trips_i64_min() {
+        let input = format!("i{}e", i64::MIN);
+        let opts = DecodeOptions { strict: true, ..DecodeOptions::default() };
+        let obj = Decoder::with_options(Cursor::new(input.into_bytes()), opts).decode().unwrap();
+        assert_eq!(obj, BenObject::I(i64::MIN));
+    }
+
+    #[test]
+    fn strict_mode_rejects_leading_zero() {
+        let opts = DecodeOptions { strict: true, ..DecodeOptions::default() };
+        let result = Decoder::with_options(Cursor::new(b"i007e".to_vec()), opts).decode();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn strict_mode_rejects_negative_zero() {
+        let opts = DecodeOptions { strict: true, ..DecodeOptions::default() };
+        let result = Decoder::with_options(Cursor::new(b"i-0e".to_vec()), opts).decode();
+        assert!(result.is_err());
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    #[test]
+    fn strict_mode_rejects_i64_overflow() {
+        let opts = DecodeOptions { strict: true, ..DecodeOptions::default() };
+        let result = Decoder::with_options(Cursor::new(b"i99999999999999999999e".to_vec()), opts).decode();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn duplicate_key_last_wins_by_default() {
+        let obj = Decoder::new(Cursor::new(b"d1:ai1e1:ai2ee".to_vec())).decode().unwrap();
+        let dict = obj.as_dict().unwrap();
+        assert_eq!(dict[&ByteString(b"a".to_vec())].as_int(), Some(2));
+    }
+
+    #[test]
+    fn duplicate_key_errors_when_configured() {
+        let opts = DecodeOptions { on_duplicate_key: DuplicateKeyPolicy::Error, ..DecodeOptions::default() };
+        let result = Decoder::with_options(Cursor::new(b"d1:ai1e1:ai2ee".to_vec()), opts).decode();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn spans_locate_nested_info_dict_verbatim() {
+        let input: &[u8] = b"d4:infod6:lengthi10ee8:announce3:urle";
+        let (_obj, span) = Decoder::new(Cursor::new(input.to_vec())).decode_with_spans().unwrap();
+        let entries = match span.node {
+            SpanNode::Dict(entries) => entries,
+            _ => panic!("expected dict span")
+        };
+        let info_span = &entries.iter().find(|(k, _)| k.0 == b"info").unwrap().1;
+        let slice = &input[info_span.start as usize..info_span.end as usize];
+        assert_eq!(slice, &b"d6:lengthi10ee"[..]);
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn bigint_feature_narrows_back_to_i64_when_it_fits() {
+        let obj = Decoder::new(Cursor::new(b"i42e".to_vec())).decode().unwrap();
+        assert_eq!(obj, BenObject::I(42));
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn bigint_feature_keeps_out_of_range_values_as_big() {
+        let huge = "99999999999999999999999999";
+        let obj = Decoder::new(Cursor::new(format!("i{}e", huge).into_bytes())).decode().unwrap();
+        match obj {
+            BenObject::Big(ref b) => assert_eq!(b.to_string(), huge),
+            _ => panic!("expected Big variant")
+        }
+    }
+}